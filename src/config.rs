@@ -0,0 +1,314 @@
+use std::io;
+use std::time::Duration;
+
+/// Current structured config file. Replaces the old bare-path
+/// `SPTSMconfig.txt`, which is migrated into this format on first run.
+pub const CONFIG_FILE: &str = "SPTSMconfig.toml";
+/// Pre-profile config format: either a bare path, or `key = value` lines.
+pub const LEGACY_CONFIG_FILE: &str = "SPTSMconfig.txt";
+
+/// A structured launch profile plus the manager's own settings, loaded from
+/// `SPTSMconfig.toml`.
+///
+/// `args` and the values in `env` support placeholder substitution, resolved
+/// at launch time via [`substitute_placeholders`]: `{server_dir}` (the
+/// executable's directory), `{exe_dir}` (this manager's own directory), and
+/// `{date}` (today as `YYYY-MM-DD`).
+#[derive(Clone)]
+pub struct Config {
+    pub server_path: String,
+    pub working_dir: Option<String>,
+    pub args: Vec<String>,
+    pub env: Vec<(String, String)>,
+    pub shutdown_timeout: Duration,
+    pub graceful_command: String,
+    pub auto_restart: bool,
+    pub control_socket_enabled: bool,
+    pub control_socket_addr: String,
+    pub log_enabled: bool,
+    pub log_retention_days: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            server_path: String::new(),
+            working_dir: None,
+            args: Vec::new(),
+            env: Vec::new(),
+            shutdown_timeout: Duration::from_secs(15),
+            graceful_command: "exit".to_string(),
+            auto_restart: true,
+            control_socket_enabled: false,
+            log_enabled: true,
+            log_retention_days: 7,
+            control_socket_addr: "127.0.0.1:9977".to_string(),
+        }
+    }
+}
+
+/// Replaces `{server_dir}`, `{exe_dir}` and `{date}` placeholders in a
+/// launch profile value with their resolved values.
+pub fn substitute_placeholders(value: &str, server_dir: &str, exe_dir: &str) -> String {
+    value
+        .replace("{server_dir}", server_dir)
+        .replace("{exe_dir}", exe_dir)
+        .replace("{date}", &crate::time_util::today_string())
+}
+
+fn unquote(value: &str) -> String {
+    let trimmed = value.trim();
+    trimmed
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(trimmed)
+        .replace("\\\"", "\"")
+        .replace("\\\\", "\\")
+}
+
+/// Splits a `[...]` array literal on top-level commas, treating `"..."`
+/// spans as opaque so a comma inside a quoted value (e.g. `"foo,bar"`)
+/// doesn't get mistaken for an item separator.
+fn split_top_level(inner: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut escaped = false;
+
+    for c in inner.chars() {
+        if escaped {
+            current.push(c);
+            escaped = false;
+            continue;
+        }
+
+        match c {
+            '\\' if in_quotes => {
+                current.push(c);
+                escaped = true;
+            }
+            '"' => {
+                current.push(c);
+                in_quotes = !in_quotes;
+            }
+            ',' if !in_quotes => items.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    items.push(current);
+
+    items
+}
+
+fn parse_string_array(value: &str) -> Vec<String> {
+    let inner = value.trim().trim_start_matches('[').trim_end_matches(']');
+    split_top_level(inner)
+        .iter()
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(unquote)
+        .collect()
+}
+
+/// Parses `SPTSMconfig.toml`: `key = value` pairs at the top level (strings
+/// quoted, `args` a quoted-string array), plus a `[env]` table for
+/// environment variables. A line starting with `#` is a comment; a `#`
+/// after a value is not treated as one.
+pub fn parse_config(contents: &str) -> Config {
+    let mut config = Config::default();
+    let mut in_env_section = false;
+
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if line.starts_with('[') && line.ends_with(']') {
+            in_env_section = line.trim_matches(|c| c == '[' || c == ']').trim() == "env";
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim();
+        let value = value.trim();
+
+        if in_env_section {
+            config.env.push((key.to_string(), unquote(value)));
+            continue;
+        }
+
+        match key {
+            "server_path" => config.server_path = unquote(value),
+            "working_dir" => config.working_dir = Some(unquote(value)),
+            "args" => config.args = parse_string_array(value),
+            "shutdown_timeout_ms" => {
+                if let Ok(ms) = value.parse::<u64>() {
+                    config.shutdown_timeout = Duration::from_millis(ms);
+                }
+            }
+            "graceful_command" => config.graceful_command = unquote(value),
+            "auto_restart" => {
+                if let Ok(enabled) = value.parse::<bool>() {
+                    config.auto_restart = enabled;
+                }
+            }
+            "control_socket_enabled" => {
+                if let Ok(enabled) = value.parse::<bool>() {
+                    config.control_socket_enabled = enabled;
+                }
+            }
+            "control_socket_addr" => config.control_socket_addr = unquote(value),
+            "log_enabled" => {
+                if let Ok(enabled) = value.parse::<bool>() {
+                    config.log_enabled = enabled;
+                }
+            }
+            "log_retention_days" => {
+                if let Ok(days) = value.parse::<usize>() {
+                    config.log_retention_days = days;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    config
+}
+
+/// Parses the legacy `SPTSMconfig.txt`: either a bare server path, or the
+/// `key = value` settings format used before launch profiles existed.
+pub fn parse_legacy_config(contents: &str) -> Config {
+    let mut config = Config::default();
+    let mut saw_key_value = false;
+
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim();
+        let value = value.trim();
+        saw_key_value = true;
+
+        match key {
+            "server_path" => config.server_path = value.to_string(),
+            "shutdown_timeout_ms" => {
+                if let Ok(ms) = value.parse::<u64>() {
+                    config.shutdown_timeout = Duration::from_millis(ms);
+                }
+            }
+            "graceful_command" => config.graceful_command = value.to_string(),
+            "auto_restart" => {
+                if let Ok(enabled) = value.parse::<bool>() {
+                    config.auto_restart = enabled;
+                }
+            }
+            "control_socket_enabled" => {
+                if let Ok(enabled) = value.parse::<bool>() {
+                    config.control_socket_enabled = enabled;
+                }
+            }
+            "control_socket_addr" => config.control_socket_addr = value.to_string(),
+            _ => {}
+        }
+    }
+
+    if !saw_key_value {
+        config.server_path = contents.trim().to_string();
+    }
+
+    config
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Serializes a `Config` to the `SPTSMconfig.toml` format.
+pub fn to_toml(config: &Config) -> String {
+    let mut out = String::new();
+    out.push_str(&format!("server_path = {}\n", quote(&config.server_path)));
+    if let Some(working_dir) = &config.working_dir {
+        out.push_str(&format!("working_dir = {}\n", quote(working_dir)));
+    }
+    let args = config
+        .args
+        .iter()
+        .map(|a| quote(a))
+        .collect::<Vec<_>>()
+        .join(", ");
+    out.push_str(&format!("args = [{}]\n", args));
+    out.push_str(&format!(
+        "shutdown_timeout_ms = {}\n",
+        config.shutdown_timeout.as_millis()
+    ));
+    out.push_str(&format!(
+        "graceful_command = {}\n",
+        quote(&config.graceful_command)
+    ));
+    out.push_str(&format!("auto_restart = {}\n", config.auto_restart));
+    out.push_str(&format!(
+        "control_socket_enabled = {}\n",
+        config.control_socket_enabled
+    ));
+    out.push_str(&format!(
+        "control_socket_addr = {}\n",
+        quote(&config.control_socket_addr)
+    ));
+    out.push_str(&format!("log_enabled = {}\n", config.log_enabled));
+    out.push_str(&format!(
+        "log_retention_days = {}\n",
+        config.log_retention_days
+    ));
+
+    if !config.env.is_empty() {
+        out.push_str("\n[env]\n");
+        for (key, value) in &config.env {
+            out.push_str(&format!("{} = {}\n", key, quote(value)));
+        }
+    }
+
+    out
+}
+
+pub fn load_config(path: &str) -> io::Result<Config> {
+    let contents = std::fs::read_to_string(path)?;
+    Ok(parse_config(&contents))
+}
+
+/// Loads `SPTSMconfig.toml` if present; otherwise migrates `SPTSMconfig.txt`
+/// (if present) into the new format, writing it out so future runs skip the
+/// migration. Returns `None` if neither file exists yet.
+pub fn load_or_migrate(toml_path: &str, legacy_path: &str) -> Option<Config> {
+    if let Ok(config) = load_config(toml_path) {
+        return Some(config);
+    }
+
+    let legacy_contents = std::fs::read_to_string(legacy_path).ok()?;
+    let config = parse_legacy_config(&legacy_contents);
+
+    if let Err(e) = std::fs::write(toml_path, to_toml(&config)) {
+        eprintln!(
+            "Failed to migrate {} to {}: {}",
+            legacy_path, toml_path, e
+        );
+    } else {
+        println!(
+            "- Migrated {} to the new {} launch profile format.",
+            legacy_path, toml_path
+        );
+    }
+
+    Some(config)
+}
+
+pub fn save_config(path: &str, config: &Config) -> io::Result<()> {
+    std::fs::write(path, to_toml(config))
+}
@@ -0,0 +1,74 @@
+use crate::{CommandMessage, LogTag};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+/// Sockets of currently-connected control clients, shared with the piped
+/// output readers so server console lines can be streamed back to them.
+pub type Clients = Arc<Mutex<Vec<TcpStream>>>;
+
+/// Starts the local control socket, off by default and gated behind
+/// `control_socket_enabled` in the config. Accepts one connection per
+/// client and maps line-delimited commands onto the same `CommandMessage`s
+/// the console input thread already sends down `cmd_tx`.
+pub fn spawn(addr: &str, cmd_tx: mpsc::Sender<CommandMessage>) -> io::Result<Clients> {
+    let listener = TcpListener::bind(addr)?;
+    let clients: Clients = Arc::new(Mutex::new(Vec::new()));
+
+    println!(
+        "{} Control socket listening on {}",
+        LogTag::ServerManager.tag(),
+        addr
+    );
+
+    let accept_clients = clients.clone();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else { continue };
+            if let Ok(writer) = stream.try_clone() {
+                accept_clients.lock().unwrap().push(writer);
+            }
+            let cmd_tx = cmd_tx.clone();
+            thread::spawn(move || handle_client(stream, cmd_tx));
+        }
+    });
+
+    Ok(clients)
+}
+
+fn handle_client(stream: TcpStream, cmd_tx: mpsc::Sender<CommandMessage>) {
+    let reader = BufReader::new(stream);
+    for line in reader.lines() {
+        let Ok(line) = line else { break };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let _ = cmd_tx.send(parse_command(line));
+    }
+}
+
+fn parse_command(line: &str) -> CommandMessage {
+    let (head, rest) = match line.split_once(' ') {
+        Some((head, rest)) => (head, rest.trim()),
+        None => (line, ""),
+    };
+
+    match head {
+        "start" => CommandMessage::StartServer,
+        "stop" => CommandMessage::StopServer,
+        "restart" => CommandMessage::RestartServer,
+        "exit" => CommandMessage::Exit,
+        "setpath" if !rest.is_empty() => CommandMessage::UpdateServerPath(rest.to_string()),
+        // Anything else is forwarded verbatim to the server's stdin.
+        _ => CommandMessage::SendCommand(line.to_string()),
+    }
+}
+
+/// Sends a line of piped server output to every connected control client,
+/// dropping any that have disconnected.
+pub fn broadcast(clients: &Clients, line: &str) {
+    let mut guard = clients.lock().unwrap();
+    guard.retain_mut(|client| writeln!(client, "{}", line).is_ok());
+}
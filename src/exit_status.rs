@@ -0,0 +1,64 @@
+use std::process::ExitStatus;
+
+/// Why the managed server (or the manager itself) stopped.
+///
+/// Surfaced in the console log and broadcast to control-socket clients
+/// together with its exit code below. The manager's own process exits with
+/// `ConfigError`'s code when the server cannot be started at launch or the
+/// server path has no parent directory, and with `UserRequested`'s code when
+/// the user quits with `exit`. The manager does not terminate on a server
+/// crash, so `Crash`'s code is never its own process exit code.
+///
+/// | Category       | Exit code |
+/// |----------------|-----------|
+/// | Clean          | 0         |
+/// | UserRequested  | 0         |
+/// | Crash          | 1         |
+/// | ConfigError    | 3         |
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ExitCategory {
+    /// The server process exited on its own with a success status.
+    Clean,
+    /// The server process exited with a non-zero/abnormal status, or had to
+    /// be force-killed because it did not stop in time.
+    Crash,
+    /// The configured executable could not be found or spawned.
+    ConfigError,
+    /// The user asked the manager itself to shut down (the `exit` command).
+    UserRequested,
+}
+
+impl ExitCategory {
+    pub fn exit_code(self) -> i32 {
+        match self {
+            ExitCategory::Clean | ExitCategory::UserRequested => 0,
+            ExitCategory::Crash => 1,
+            ExitCategory::ConfigError => 3,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ExitCategory::Clean => "clean exit",
+            ExitCategory::Crash => "server crash",
+            ExitCategory::ConfigError => "config/path error",
+            ExitCategory::UserRequested => "user-requested exit",
+        }
+    }
+
+    pub fn log_tag(self) -> crate::LogTag {
+        match self {
+            ExitCategory::Clean | ExitCategory::UserRequested => crate::LogTag::ServerManager,
+            ExitCategory::Crash | ExitCategory::ConfigError => crate::LogTag::Error,
+        }
+    }
+}
+
+/// Classifies a finished child process by its exit status.
+pub fn classify_exit_status(status: ExitStatus) -> ExitCategory {
+    if status.success() {
+        ExitCategory::Clean
+    } else {
+        ExitCategory::Crash
+    }
+}
@@ -0,0 +1,96 @@
+use crate::time_util;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Strips ANSI escape sequences (color codes, cursor movement, ...) so log
+/// files stay plain text while the console keeps its colors.
+pub fn strip_ansi(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' && chars.peek() == Some(&'[') {
+            chars.next(); // consume '['
+            for next in chars.by_ref() {
+                if next.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+            continue;
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Tees piped server output to a plain-text, rotating log file under
+/// `<server_dir>/logs/`, one file per calendar day. Whenever a log file is
+/// opened (the first write, and each rollover to a new day), only the newest
+/// `retention_days` `.log` files in that directory, ordered by file name, are
+/// kept; the rest are deleted.
+pub struct LogWriter {
+    dir: PathBuf,
+    retention_days: usize,
+    current_date: String,
+    file: Option<File>,
+}
+
+impl LogWriter {
+    pub fn new(server_dir: &str, retention_days: usize) -> Self {
+        let dir = Path::new(server_dir).join("logs");
+        if let Err(e) = fs::create_dir_all(&dir) {
+            eprintln!("Failed to create log directory {}: {}", dir.display(), e);
+        }
+
+        LogWriter {
+            dir,
+            retention_days,
+            current_date: String::new(),
+            file: None,
+        }
+    }
+
+    fn file_for_today(&mut self) -> Option<&mut File> {
+        let today = time_util::today_string();
+        if self.file.is_none() || self.current_date != today {
+            let path = self.dir.join(format!("server-{}.log", today));
+            match OpenOptions::new().create(true).append(true).open(&path) {
+                Ok(file) => {
+                    self.file = Some(file);
+                    self.current_date = today;
+                    self.prune_old_logs();
+                }
+                Err(e) => {
+                    eprintln!("Failed to open log file {}: {}", path.display(), e);
+                    self.file = None;
+                }
+            }
+        }
+        self.file.as_mut()
+    }
+
+    fn prune_old_logs(&self) {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return;
+        };
+        let mut log_files: Vec<_> = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "log"))
+            .collect();
+        log_files.sort_by_key(|entry| entry.file_name());
+
+        while log_files.len() > self.retention_days {
+            let oldest = log_files.remove(0);
+            let _ = fs::remove_file(oldest.path());
+        }
+    }
+
+    /// Writes one tagged, ANSI-stripped line (e.g. tag `"STDOUT"`/`"STDERR"`).
+    pub fn write_line(&mut self, tag: &str, line: &str) {
+        let plain = strip_ansi(line);
+        let Some(file) = self.file_for_today() else {
+            return;
+        };
+        let _ = writeln!(file, "[{}] [{}] {}", time_util::now_time_string(), tag, plain);
+    }
+}
@@ -1,8 +1,17 @@
+mod config;
+mod control_socket;
+mod exit_status;
+mod logging;
+mod supervisor;
+mod time_util;
+
 use std::io::{self, BufRead, Write};
 use std::path::Path;
 use std::process::{Child, Command, Stdio};
-use std::sync::mpsc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
 
 macro_rules! erase_line {
     () => {
@@ -12,7 +21,7 @@ macro_rules! erase_line {
 }
 
 #[allow(dead_code)]
-enum CommandMessage {
+pub(crate) enum CommandMessage {
     StartServer,
     StopServer,
     RestartServer,
@@ -21,7 +30,7 @@ enum CommandMessage {
     Exit,
 }
 
-enum LogTag {
+pub(crate) enum LogTag {
     ServerManager,
     ConsoleInput,
     Warning,
@@ -31,7 +40,7 @@ enum LogTag {
 }
 
 impl LogTag {
-    fn tag(&self) -> &'static str {
+    pub(crate) fn tag(&self) -> &'static str {
         match self {
             LogTag::ServerManager => "\x1b[30m\x1b[48;5;134m[Server-Manager]\x1b[0m",
             LogTag::ConsoleInput => "\x1b[30m\x1b[48;5;195m > \x1b[0m",
@@ -43,7 +52,7 @@ impl LogTag {
     }
 }
 
-enum Color {
+pub(crate) enum Color {
     Red,
     Green,
     Blue,
@@ -55,7 +64,7 @@ enum Color {
 }
 
 impl Color {
-    fn text(&self) -> &'static str {
+    pub(crate) fn text(&self) -> &'static str {
         match self {
             Color::Red => "\x1b[31m",
             Color::Green => "\x1b[32m",
@@ -72,24 +81,55 @@ impl Color {
 struct ServerManager {
     server_path: String,
     server_dir: String,
-    current_process: Option<Child>,
+    exe_dir: String,
+    working_dir: Option<String>,
+    args: Vec<String>,
+    env: Vec<(String, String)>,
+    current_process: Arc<Mutex<Option<Child>>>,
+    stopping: Arc<AtomicBool>,
+    shutdown_timeout: Duration,
+    graceful_command: String,
+    socket_clients: Option<control_socket::Clients>,
+    log_writer: Option<Arc<Mutex<logging::LogWriter>>>,
+    last_exit_category: Arc<Mutex<exit_status::ExitCategory>>,
 }
 
 impl ServerManager {
-    fn new(server_path: &str) -> Self {
-        let server_dir = Path::new(server_path)
+    fn new(
+        config: &config::Config,
+        exe_dir: String,
+        current_process: Arc<Mutex<Option<Child>>>,
+        stopping: Arc<AtomicBool>,
+        socket_clients: Option<control_socket::Clients>,
+        last_exit_category: Arc<Mutex<exit_status::ExitCategory>>,
+    ) -> Self {
+        let server_dir = Path::new(&config.server_path)
             .parent()
             .unwrap_or_else(|| {
                 eprintln!("Invalid server path provided.");
-                std::process::exit(1);
+                std::process::exit(exit_status::ExitCategory::ConfigError.exit_code());
             })
             .to_string_lossy()
             .into_owned();
 
+        let log_writer = config
+            .log_enabled
+            .then(|| Arc::new(Mutex::new(logging::LogWriter::new(&server_dir, config.log_retention_days))));
+
         ServerManager {
-            server_path: server_path.to_string(),
+            server_path: config.server_path.clone(),
             server_dir,
-            current_process: None,
+            exe_dir,
+            working_dir: config.working_dir.clone(),
+            args: config.args.clone(),
+            env: config.env.clone(),
+            current_process,
+            stopping,
+            shutdown_timeout: config.shutdown_timeout,
+            graceful_command: config.graceful_command.clone(),
+            socket_clients,
+            log_writer,
+            last_exit_category,
         }
     }
 
@@ -97,61 +137,143 @@ impl ServerManager {
         // Ensure any existing process is terminated
         self.stop_server();
 
-        // Start new server process
-        let child = Command::new(&self.server_path)
-            .current_dir(&self.server_dir)
+        let working_dir = self
+            .working_dir
+            .as_deref()
+            .map(|dir| config::substitute_placeholders(dir, &self.server_dir, &self.exe_dir))
+            .unwrap_or_else(|| self.server_dir.clone());
+
+        let mut command = Command::new(&self.server_path);
+        command
+            .current_dir(working_dir)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
-            .stdin(Stdio::piped())
-            .spawn()?;
+            .stdin(Stdio::piped());
+
+        for arg in &self.args {
+            command.arg(config::substitute_placeholders(arg, &self.server_dir, &self.exe_dir));
+        }
+        for (key, value) in &self.env {
+            command.env(key, config::substitute_placeholders(value, &self.server_dir, &self.exe_dir));
+        }
+
+        // Start new server process
+        let child = command.spawn()?;
 
         println!("{} Server started with PID: {}{}{}\n",LogTag::ServerManager.tag(), Color::Magenta.text() ,child.id(), Color::Reset.text());
-        self.current_process = Some(child);
+        *self.current_process.lock().unwrap() = Some(child);
+        // The stop we just did (if any) has been fully accounted for; don't
+        // let it linger and swallow this new process's first real crash.
+        self.stopping.store(false, Ordering::SeqCst);
         Ok(())
     }
 
+    const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
     fn stop_server(&mut self) {
-        if let Some(mut process) = self.current_process.take() {
+        // Mark this as an intentional stop so the crash-detection supervisor
+        // doesn't treat the exit it's about to observe as a crash.
+        self.stopping.store(true, Ordering::SeqCst);
+
+        let taken = self.current_process.lock().unwrap().take();
+        if let Some(mut process) = taken {
             // Attempt to gracefully terminate the server
             if let Some(ref mut stdin) = process.stdin {
-                let _ = stdin.write_all(b"exit\n");
+                let _ = stdin.write_all(format!("{}\n", self.graceful_command).as_bytes());
                 let _ = stdin.flush();
             }
 
-            // Give the server some time to shut down gracefully
-            thread::sleep(std::time::Duration::from_secs(2));
+            let start = Instant::now();
+            let mut exit_status = None;
+            let mut poll_failed = false;
 
-            // Check if the process has exited
-            match process.try_wait() {
-                Ok(Some(_status)) => {
-                    println!("Server stopped gracefully");
-                }
-                Ok(None) => {
-                    // Force kill if still running
-                    let _ = process.kill();
-                    let _ = process.wait();
-                    println!("Server stopped forcefully");
+            // Poll instead of a fixed sleep, so a fast shutdown isn't held up
+            // and a slow one (database flush, profile save) isn't cut short.
+            while start.elapsed() < self.shutdown_timeout {
+                match process.try_wait() {
+                    Ok(Some(status)) => {
+                        exit_status = Some(status);
+                        break;
+                    }
+                    Ok(None) => thread::sleep(Self::POLL_INTERVAL),
+                    Err(e) => {
+                        eprintln!("{} Error while stopping server: {}", LogTag::Error.tag(), e);
+                        poll_failed = true;
+                        break;
+                    }
                 }
-                Err(e) => {
-                    eprintln!("{} Error while stopping server: {}", LogTag::Error.tag(), e);
+            }
+
+            let elapsed = start.elapsed();
+            let category = if poll_failed {
+                // We can no longer trust try_wait() on this child; force-kill
+                // it rather than leave a possibly-orphaned process behind.
+                let _ = process.kill();
+                let _ = process.wait();
+                exit_status::ExitCategory::Crash
+            } else if let Some(status) = exit_status {
+                let category = exit_status::classify_exit_status(status);
+                match category {
+                    exit_status::ExitCategory::Clean => println!(
+                        "{} Server stopped gracefully after {:.1}s",
+                        LogTag::ServerManager.tag(),
+                        elapsed.as_secs_f32()
+                    ),
+                    _ => println!(
+                        "{} Server exited after {:.1}s with a non-zero status",
+                        LogTag::ServerManager.tag(),
+                        elapsed.as_secs_f32()
+                    ),
                 }
+                category
+            } else {
+                let _ = process.kill();
+                println!(
+                    "{} Server did not stop within {:.1}s, force-killed after {:.1}s",
+                    LogTag::ServerManager.tag(),
+                    self.shutdown_timeout.as_secs_f32(),
+                    elapsed.as_secs_f32()
+                );
+                // A forced kill is never a clean exit, regardless of the
+                // (killed) process's resulting status.
+                let _ = process.wait();
+                exit_status::ExitCategory::Crash
+            };
+
+            println!(
+                "{} Exit category: {}",
+                category.log_tag().tag(),
+                category.label()
+            );
+            *self.last_exit_category.lock().unwrap() = category;
+            if let Some(ref clients) = self.socket_clients {
+                control_socket::broadcast(
+                    clients,
+                    &format!("EXIT_STATUS {} code={}", category.label(), category.exit_code()),
+                );
             }
         }
     }
 
     fn pipe_output(&mut self) -> io::Result<()> {
-        if let Some(ref mut process) = self.current_process {
+        let mut guard = self.current_process.lock().unwrap();
+        if let Some(ref mut process) = *guard {
             if let Some(stdout) = process.stdout.take() {
-                Self::pipe_stream(stdout, false);
+                Self::pipe_stream(stdout, false, self.socket_clients.clone(), self.log_writer.clone());
             }
             if let Some(stderr) = process.stderr.take() {
-                Self::pipe_stream(stderr, true);
+                Self::pipe_stream(stderr, true, self.socket_clients.clone(), self.log_writer.clone());
             }
         }
         Ok(())
     }
 
-    fn pipe_stream<R: 'static + Send + io::Read>(reader: R, is_stderr: bool) {
+    fn pipe_stream<R: 'static + Send + io::Read>(
+        reader: R,
+        is_stderr: bool,
+        clients: Option<control_socket::Clients>,
+        log_writer: Option<Arc<Mutex<logging::LogWriter>>>,
+    ) {
         let buf_reader = io::BufReader::new(reader);
         thread::spawn(move || {
             for line in buf_reader.lines() {
@@ -162,6 +284,13 @@ impl ServerManager {
                         } else {
                             println!("{}", line);
                         }
+                        if let Some(ref clients) = clients {
+                            control_socket::broadcast(clients, &line);
+                        }
+                        if let Some(ref log_writer) = log_writer {
+                            let tag = if is_stderr { "STDERR" } else { "STDOUT" };
+                            log_writer.lock().unwrap().write_line(tag, &line);
+                        }
                     }
                     Err(e) => {
                         eprintln!("{} Error reading process output: {}", LogTag::Error.tag(), e);
@@ -172,8 +301,23 @@ impl ServerManager {
         });
     }
 
+    /// Classifies an in-session failure (a `setpath`/`restart` hitting a bad
+    /// or missing executable path) as `ConfigError` and broadcasts it the
+    /// same way `stop_server` reports the server's own exit category.
+    fn report_config_error(&self) {
+        let category = exit_status::ExitCategory::ConfigError;
+        *self.last_exit_category.lock().unwrap() = category;
+        if let Some(ref clients) = self.socket_clients {
+            control_socket::broadcast(
+                clients,
+                &format!("EXIT_STATUS {} code={}", category.label(), category.exit_code()),
+            );
+        }
+    }
+
     fn send_command(&mut self, command: &str) -> io::Result<()> {
-        if let Some(ref mut process) = self.current_process {
+        let mut guard = self.current_process.lock().unwrap();
+        if let Some(ref mut process) = *guard {
             if let Some(ref mut stdin) = process.stdin {
                 writeln!(stdin, "{}", command)?;
             } else {
@@ -210,17 +354,26 @@ fn display_help() {
 
 fn main() -> io::Result<()> {
 
-    // Attempt to read the server path from a config file
-    let server_path = match std::fs::read_to_string("SPTSMconfig.txt") {
-        Ok(path) => clean_path(&path),
-        Err(_) => {
+    // Load the structured launch profile, migrating the legacy bare-path
+    // SPTSMconfig.txt into SPTSMconfig.toml on first run if present.
+    let loaded_config = config::load_or_migrate(config::CONFIG_FILE, config::LEGACY_CONFIG_FILE);
+
+    let mut active_config = match loaded_config {
+        Some(cfg) if !cfg.server_path.is_empty() => {
+            let mut cfg = cfg;
+            cfg.server_path = clean_path(&cfg.server_path);
+            cfg
+        }
+        other => {
+            let mut cfg = other.unwrap_or_default();
+
             // If reading the config file fails, attempt to find the server in the same directory
             let current_exe = std::env::current_exe()?;
             let current_dir = current_exe.parent().unwrap();
             let default_server_path = current_dir.join("SPT.Server.exe");
 
             if default_server_path.exists() {
-                default_server_path.to_string_lossy().to_string()
+                cfg.server_path = default_server_path.to_string_lossy().to_string();
             } else {
                 // Prompt the user to input the server path
                 println!(
@@ -240,7 +393,7 @@ fn main() -> io::Result<()> {
                 let mut input = String::new();
                 io::stdin().read_line(&mut input)?;
                 print!("{}", Color::Reset.text());
-                let input = clean_path(&input);
+                cfg.server_path = clean_path(&input);
 
                 // Ask if the user wants to remember this path
                 print!(
@@ -256,19 +409,57 @@ fn main() -> io::Result<()> {
                 let mut remember = String::new();
                 io::stdin().read_line(&mut remember)?;
                 if remember.trim().eq_ignore_ascii_case("Y") {
-                    // Save the path to the config file
-                    std::fs::write("SPTSMconfig.txt", &input)?;
+                    // Save the profile to the config file
+                    config::save_config(config::CONFIG_FILE, &cfg)?;
                 }
                 print!("{}", Color::Reset.text());
-
-                input
             }
+
+            cfg
         }
     };
 
     println!(
-        "- Using server path: {}{}{}\n\n", Color::Blurp.text(), server_path, Color::Reset.text());
-    let mut server_manager = ServerManager::new(&server_path);
+        "- Using server path: {}{}{}\n\n", Color::Blurp.text(), active_config.server_path, Color::Reset.text());
+
+    let exe_dir = std::env::current_exe()?
+        .parent()
+        .unwrap()
+        .to_string_lossy()
+        .into_owned();
+
+    let current_process: Arc<Mutex<Option<Child>>> = Arc::new(Mutex::new(None));
+    let stopping = Arc::new(AtomicBool::new(false));
+    let last_exit_category = Arc::new(Mutex::new(exit_status::ExitCategory::Clean));
+
+    // Create a channel for command communication
+    let (cmd_tx, cmd_rx) = mpsc::channel::<CommandMessage>();
+
+    let socket_clients = if active_config.control_socket_enabled {
+        match control_socket::spawn(&active_config.control_socket_addr, cmd_tx.clone()) {
+            Ok(clients) => Some(clients),
+            Err(e) => {
+                eprintln!(
+                    "{} Failed to start control socket on {}: {}",
+                    LogTag::Error.tag(),
+                    active_config.control_socket_addr,
+                    e
+                );
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let mut server_manager = ServerManager::new(
+        &active_config,
+        exe_dir.clone(),
+        current_process.clone(),
+        stopping.clone(),
+        socket_clients.clone(),
+        last_exit_category.clone(),
+    );
 
     // Start the server
     if let Err(e) = server_manager.start_server() {
@@ -280,7 +471,7 @@ fn main() -> io::Result<()> {
         // Wait for the user to press Enter
         let _ = io::stdin().read_line(&mut String::new());
 
-        return Ok(());
+        std::process::exit(exit_status::ExitCategory::ConfigError.exit_code());
     }
 
     // Cleans the input path by removing leading/trailing quotes, replacing backslashes with forward slashes,
@@ -299,11 +490,20 @@ fn main() -> io::Result<()> {
     // Pipe output
     server_manager.pipe_output()?;
 
-    // Create a channel for command communication
-    let (cmd_tx, cmd_rx) = mpsc::channel::<CommandMessage>();
+    // Watch for the server exiting on its own and auto-restart it
+    if active_config.auto_restart {
+        supervisor::spawn(
+            current_process.clone(),
+            stopping.clone(),
+            cmd_tx.clone(),
+            last_exit_category.clone(),
+            socket_clients.clone(),
+        );
+    }
 
     // Spawn a thread to handle user input
     let cmd_tx_clone = cmd_tx.clone();
+    let config_for_setpath = active_config.clone();
     let input_thread = thread::spawn(move || {
         println!("Type '{}help{}' to see available commands.\n", Color::Blue.text(), Color::Reset.text());
         loop {
@@ -353,8 +553,10 @@ fn main() -> io::Result<()> {
                     io::stdin().read_line(&mut remember).unwrap();
 
                     if remember.trim().eq_ignore_ascii_case("Y") {
-                        // Save the path to the config file
-                        if let Err(e) = std::fs::write("SPTSMconfig.txt", &new_path) {
+                        // Save the profile (with the new path) to the config file
+                        let mut updated = config_for_setpath.clone();
+                        updated.server_path = new_path.clone();
+                        if let Err(e) = config::save_config(config::CONFIG_FILE, &updated) {
                             eprintln!("{} Failed to save path to config file: {}", LogTag::Error.tag(), e);
                         }
                     }
@@ -375,7 +577,8 @@ fn main() -> io::Result<()> {
         match message {
             CommandMessage::StartServer => {
                 if let Err(e) = server_manager.start_server() {
-                    eprintln!("Failed to start server: {}", e);
+                    eprintln!("{} Failed to start server: {}", LogTag::Error.tag(), e);
+                    server_manager.report_config_error();
                 } else {
                     let _ = server_manager.pipe_output();
                 }
@@ -387,6 +590,7 @@ fn main() -> io::Result<()> {
                 server_manager.stop_server();
                 if let Err(e) = server_manager.start_server() {
                     eprintln!("{} Failed to restart server: {}", LogTag::Error.tag(), e);
+                    server_manager.report_config_error();
                 } else {
                     let _ = server_manager.pipe_output();
                 }
@@ -398,15 +602,27 @@ fn main() -> io::Result<()> {
             }
             CommandMessage::UpdateServerPath(new_path) => {
                 server_manager.stop_server();
-                server_manager = ServerManager::new(&new_path);
+                active_config.server_path = new_path;
+                server_manager = ServerManager::new(
+                    &active_config,
+                    exe_dir.clone(),
+                    current_process.clone(),
+                    stopping.clone(),
+                    socket_clients.clone(),
+                    last_exit_category.clone(),
+                );
                 if let Err(e) = server_manager.start_server() {
                     eprintln!("{} Failed to start server with new path: {}", LogTag::Error.tag(), e);
+                    server_manager.report_config_error();
                 } else {
                     let _ = server_manager.pipe_output();
                 }
             }
             CommandMessage::Exit => {
                 server_manager.stop_server();
+                // The user explicitly asked the manager to quit; that intent
+                // takes precedence over whatever the server's own exit looked like.
+                *last_exit_category.lock().unwrap() = exit_status::ExitCategory::UserRequested;
                 break;
             }
         }
@@ -415,5 +631,22 @@ fn main() -> io::Result<()> {
     // Wait for the input thread to finish
     input_thread.join().unwrap();
 
-    Ok(())
+    let category = *last_exit_category.lock().unwrap();
+    println!(
+        "{} Manager exiting: {}",
+        category.log_tag().tag(),
+        category.label()
+    );
+    // stop_server() already broadcast the server's own exit category, which
+    // for CommandMessage::Exit is stale (overwritten to UserRequested right
+    // after). Send the final, authoritative category so control-socket
+    // clients don't see e.g. a "server crash" broadcast for a normal
+    // user-requested shutdown.
+    if let Some(ref clients) = socket_clients {
+        control_socket::broadcast(
+            clients,
+            &format!("EXIT_STATUS {} code={}", category.label(), category.exit_code()),
+        );
+    }
+    std::process::exit(category.exit_code())
 }
@@ -0,0 +1,108 @@
+use crate::control_socket::{self, Clients};
+use crate::exit_status::ExitCategory;
+use crate::{CommandMessage, LogTag};
+use std::process::Child;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const CHECK_INTERVAL: Duration = Duration::from_millis(500);
+const INITIAL_BACKOFF: Duration = Duration::from_millis(250);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+const MAX_CONSECUTIVE_ATTEMPTS: u32 = 5;
+const STABILITY_WINDOW: Duration = Duration::from_secs(60);
+
+/// Watches the managed child process and auto-restarts it with capped
+/// exponential backoff if it exits on its own (crash). Stops watching
+/// entirely once `MAX_CONSECUTIVE_ATTEMPTS` is hit in a row, so the user
+/// can step in once a server keeps failing to come up.
+///
+/// `stopping` must be set before any user-initiated stop/restart, so the
+/// monitor doesn't mistake it for a crash.
+///
+/// Giving up is classified and broadcast over the control socket like any
+/// other exit (see [`crate::exit_status`]), so external tools watching the
+/// socket learn the server is down for good. The manager process itself
+/// stays up, interactive console and all, so the user can step in with
+/// `restart` once they've fixed the issue.
+pub fn spawn(
+    current_process: Arc<Mutex<Option<Child>>>,
+    stopping: Arc<AtomicBool>,
+    cmd_tx: mpsc::Sender<CommandMessage>,
+    last_exit_category: Arc<Mutex<ExitCategory>>,
+    socket_clients: Option<Clients>,
+) {
+    thread::spawn(move || {
+        let mut backoff = INITIAL_BACKOFF;
+        let mut attempts = 0u32;
+        let mut last_event = Instant::now();
+
+        loop {
+            thread::sleep(CHECK_INTERVAL);
+
+            let exited = {
+                let mut guard = current_process.lock().unwrap();
+                match guard.as_mut() {
+                    Some(process) => matches!(process.try_wait(), Ok(Some(_))),
+                    None => false,
+                }
+            };
+
+            if !exited {
+                if attempts > 0 && last_event.elapsed() >= STABILITY_WINDOW {
+                    attempts = 0;
+                    backoff = INITIAL_BACKOFF;
+                }
+                continue;
+            }
+
+            if stopping.swap(false, Ordering::SeqCst) {
+                // A user-initiated stop/restart set `stopping` before taking
+                // the process out of current_process; this exit was expected.
+                last_event = Instant::now();
+                continue;
+            }
+
+            if attempts >= MAX_CONSECUTIVE_ATTEMPTS {
+                eprintln!(
+                    "{} Server crashed again after {} auto-restart attempts in a row, giving up auto-restart. Use '{}restart{}' manually once the issue is fixed.",
+                    LogTag::Error.tag(),
+                    attempts,
+                    crate::Color::Blue.text(),
+                    crate::Color::Reset.text(),
+                );
+
+                // Report the give-up the same way any other exit is reported,
+                // but leave the interactive manager running: the whole point
+                // of giving up (rather than exiting) is to hand control back
+                // to the user instead of ending their session.
+                let category = ExitCategory::Crash;
+                *last_exit_category.lock().unwrap() = category;
+                if let Some(ref clients) = socket_clients {
+                    control_socket::broadcast(
+                        clients,
+                        &format!("EXIT_STATUS {} code={}", category.label(), category.exit_code()),
+                    );
+                }
+                return;
+            }
+
+            attempts += 1;
+            eprintln!(
+                "{} Server exited unexpectedly, auto-restarting in {:?} (attempt {}/{})",
+                LogTag::Error.tag(),
+                backoff,
+                attempts,
+                MAX_CONSECUTIVE_ATTEMPTS,
+            );
+            thread::sleep(backoff);
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+            last_event = Instant::now();
+
+            if cmd_tx.send(CommandMessage::RestartServer).is_err() {
+                return;
+            }
+        }
+    });
+}
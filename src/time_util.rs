@@ -0,0 +1,46 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Today's date as `YYYY-MM-DD`, computed from the system clock so that
+/// date-based features (placeholder substitution, log rotation) don't need
+/// a date/time dependency.
+pub fn today_string() -> String {
+    let (year, month, day) = civil_from_days(days_since_epoch());
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// The current wall-clock time as `HH:MM:SS`, for prefixing log lines.
+pub fn now_time_string() -> String {
+    let since_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let secs_of_day = since_epoch.as_secs() % 86400;
+    format!(
+        "{:02}:{:02}:{:02}",
+        secs_of_day / 3600,
+        (secs_of_day % 3600) / 60,
+        secs_of_day % 60
+    )
+}
+
+fn days_since_epoch() -> i64 {
+    let since_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    (since_epoch.as_secs() / 86400) as i64
+}
+
+// Howard Hinnant's civil-from-days algorithm (public domain): converts a
+// count of days since 1970-01-01 to a (year, month, day) date.
+fn civil_from_days(z: i64) -> (i64, u32, u32) {
+    let z = z + 719468;
+    let era = if z >= 0 { z } else { z - 146096 } / 146097;
+    let doe = (z - era * 146097) as u64;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let y = yoe as i64 + era * 400;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = if month <= 2 { y + 1 } else { y };
+    (year, month, day)
+}